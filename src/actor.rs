@@ -1,14 +1,16 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-#[cfg(feature = "multi")]
-use std::thread::sleep;
-#[cfg(feature = "multi")]
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use curl::easy::{Easy2, Handler};
-#[cfg(feature = "multi")]
-use curl::multi::Multi;
-use tokio::sync::mpsc::{self, Sender};
-use tokio::sync::oneshot;
+use curl::multi::{Easy2Handle, Multi};
+use curl::MultiError;
+use tokio::sync::mpsc::{self, error::TryRecvError, Sender};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 use crate::error::Error;
 /// CurlActor is responsible for performing
@@ -35,7 +37,9 @@ use crate::error::Error;
 /// ```
 ///
 /// Example for multiple request executed
-/// at the same time.
+/// at the same time. Each `CurlActor` below drives its own persistent
+/// `curl::multi::Multi` on its own background task, and the two
+/// transfers run concurrently rather than one after the other.
 ///
 /// ```
 /// use async_curl::{actor::CurlActor, response_handler::ResponseHandler};
@@ -92,6 +96,23 @@ where
     H: Handler + Debug + Send + 'static,
 {
     request_sender: Sender<Request<H>>,
+    cancel_sender: Sender<usize>,
+    next_token: Arc<AtomicUsize>,
+    concurrency_limit: Option<Arc<Semaphore>>,
+}
+
+/// Configuration for `CurlActor::with_config`.
+#[derive(Debug, Clone, Default)]
+pub struct ActorConfig {
+    /// Caps the number of transfers driven concurrently; see
+    /// `CurlActor::with_concurrency_limit`.
+    pub concurrency_limit: Option<usize>,
+    /// Caps how many connections the shared `Multi` keeps open to a
+    /// single host at once (`CURLMOPT_MAX_HOST_CONNECTIONS`).
+    pub max_host_connections: Option<usize>,
+    /// Caps the total number of connections the shared `Multi` keeps
+    /// open across all hosts at once (`CURLMOPT_MAX_TOTAL_CONNECTIONS`).
+    pub max_total_connections: Option<usize>,
 }
 
 impl<H> Default for CurlActor<H>
@@ -108,95 +129,556 @@ where
     H: Handler + Debug + Send + 'static,
 {
     /// This creates the new instance of CurlActor.
-    /// This spawns a new asynchronous task using tokio
-    /// so that it won't block. The perform_curl
-    /// function is executed when send_request is called
+    /// This spawns a single blocking task that owns a long-lived
+    /// `curl::multi::Multi` for the lifetime of the actor. Every
+    /// `Request` sent through `send_request` is added to that `Multi`
+    /// as soon as it arrives, so many transfers are driven concurrently
+    /// by one event loop instead of one `Multi` per request. Because the
+    /// `Multi` is kept alive for the actor's lifetime, successive
+    /// requests to the same host also reuse its warm connections,
+    /// TLS sessions, and DNS cache instead of paying full connection
+    /// setup every time.
     pub fn new() -> Self {
-        let (request_sender, mut request_receiver) = mpsc::channel::<Request<H>>(1);
-        tokio::spawn(async move {
-            while let Some(Request(easy2, oneshot_sender)) = request_receiver.recv().await {
-                if let Err(err) = tokio::task::spawn_blocking(move || {
-                    let response = perform_curl(easy2);
-                    if let Err(res) = oneshot_sender.send(response) {
-                        eprintln!("Warning! The receiver has been dropped. {:?}", res);
-                    }
-                })
-                .await
-                {
-                    eprintln!("Error! Join Error. {:?}", err);
-                }
-            }
+        Self::with_config(ActorConfig::default())
+    }
+
+    /// Same as `new`, but caps the number of transfers this actor will
+    /// run at once to at most `limit`. A burst of `send_request` calls
+    /// beyond `limit` will simply await their turn instead of all being
+    /// handed to the shared `Multi` (and held in memory) at once.
+    pub fn with_concurrency_limit(limit: usize) -> Self {
+        Self::with_config(ActorConfig {
+            concurrency_limit: Some(limit),
+            ..ActorConfig::default()
+        })
+    }
+
+    /// Same as `new`, but bounds the shared `Multi`'s connection cache
+    /// instead of leaving it unlimited: `max_host_connections` caps how
+    /// many connections may stay open to a single host at once, and
+    /// `max_total_connections` caps the total across all hosts. Note
+    /// that this changes the concurrency model - transfers beyond these
+    /// limits queue inside curl's own connection cache rather than each
+    /// opening a fresh connection.
+    pub fn with_connection_limits(max_host_connections: usize, max_total_connections: usize) -> Self {
+        Self::with_config(ActorConfig {
+            max_host_connections: Some(max_host_connections),
+            max_total_connections: Some(max_total_connections),
+            ..ActorConfig::default()
+        })
+    }
+
+    /// Same as `new`, but built from an explicit [`ActorConfig`] so
+    /// that a concurrency limit and connection cache limits can be
+    /// configured together.
+    pub fn with_config(config: ActorConfig) -> Self {
+        let (request_sender, request_receiver) = mpsc::channel::<Request<H>>(1024);
+        let (cancel_sender, cancel_receiver) = mpsc::channel::<usize>(1024);
+        let ActorConfig {
+            concurrency_limit,
+            max_host_connections,
+            max_total_connections,
+        } = config;
+        tokio::task::spawn_blocking(move || {
+            multi_event_loop(
+                request_receiver,
+                cancel_receiver,
+                max_host_connections,
+                max_total_connections,
+            )
         });
 
-        Self { request_sender }
+        Self {
+            request_sender,
+            cancel_sender,
+            next_token: Arc::new(AtomicUsize::new(0)),
+            concurrency_limit: concurrency_limit.map(|limit| Arc::new(Semaphore::new(limit))),
+        }
+    }
+
+    /// Returns how many more transfers can be dispatched right now
+    /// before `send_request` would start to await, or `None` if this
+    /// actor has no concurrency limit configured.
+    pub fn available_permits(&self) -> Option<usize> {
+        self.concurrency_limit
+            .as_ref()
+            .map(|semaphore| semaphore.available_permits())
+    }
+
+    /// Acquires a concurrency permit (if configured), allocates this
+    /// transfer's token, and hands the `Easy2<H>` off to the event
+    /// loop. Shared by `send_request` and its timeout/cancellation
+    /// variants below.
+    ///
+    /// The concurrency permit travels with the `Request` itself and is
+    /// held by the event loop's `InFlight` entry rather than by the
+    /// caller: it is only released once the loop actually removes the
+    /// handle from `Multi` (on completion, cancellation, or a fatal
+    /// `Multi` error), not merely when this future returns. That keeps
+    /// the configured limit meaningful even when a caller times out or
+    /// cancels while the transfer is still physically running.
+    ///
+    /// The request is handed to the channel via `Sender::reserve`
+    /// rather than a plain `send`, so a full channel applies
+    /// backpressure by waiting for room here instead of leaving the
+    /// event loop to drain a queue it can't keep up with.
+    async fn dispatch(
+        &self,
+        easy2: Easy2<H>,
+    ) -> Result<(usize, oneshot::Receiver<Result<Easy2<H>, Error<H>>>), Error<H>> {
+        let concurrency_permit = match &self.concurrency_limit {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("the semaphore is never closed for the lifetime of the actor"),
+            ),
+            None => None,
+        };
+
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<Result<Easy2<H>, Error<H>>>();
+        let request = Request(token, easy2, oneshot_sender, concurrency_permit);
+
+        match self.request_sender.reserve().await {
+            Ok(send_permit) => {
+                send_permit.send(request);
+                Ok((token, oneshot_receiver))
+            }
+            Err(_) => Err(Error::TokioSend(mpsc::error::SendError(request))),
+        }
     }
 
     /// This will trigger the request_reciever channel
     /// at the spawned asynchronous task to call
     /// perform_curl_multi to start communicating with
     /// the target server.
+    ///
+    /// When a concurrency limit is configured, this first awaits a
+    /// permit - applying backpressure to the caller - and the event
+    /// loop only releases it once the transfer has actually finished
+    /// (see `dispatch`). If the returned future is dropped before the
+    /// transfer completes (e.g. its task is aborted), the transfer is
+    /// removed from the shared `Multi` instead of being left to run to
+    /// completion.
     pub async fn send_request(&self, easy2: Easy2<H>) -> Result<Easy2<H>, Error<H>>
     where
         H: Handler + Debug + Send + 'static,
     {
-        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<Result<Easy2<H>, Error<H>>>();
-        self.request_sender
-            .send(Request(easy2, oneshot_sender))
-            .await?;
-        oneshot_receiver.await?
+        let (token, oneshot_receiver) = self.dispatch(easy2).await?;
+        let mut guard = CancelOnDrop::new(token, self.cancel_sender.clone());
+        let result = oneshot_receiver.await?;
+        guard.disarm();
+        result
+    }
+
+    /// Like `send_request`, but bounds how long the transfer may run.
+    /// If `timeout` elapses first, the transfer is removed from the
+    /// shared `Multi` and this returns `Error::Timeout` instead of
+    /// waiting for curl to finish on its own.
+    pub async fn send_request_with_timeout(
+        &self,
+        easy2: Easy2<H>,
+        timeout: Duration,
+    ) -> Result<Easy2<H>, Error<H>> {
+        let (token, oneshot_receiver) = self.dispatch(easy2).await?;
+        let mut guard = CancelOnDrop::new(token, self.cancel_sender.clone());
+
+        let result = match tokio::time::timeout(timeout, oneshot_receiver).await {
+            Ok(received) => received?,
+            Err(_) => {
+                let _ = self.cancel_sender.send(token).await;
+                Err(Error::Timeout)
+            }
+        };
+        guard.disarm();
+        result
+    }
+
+    /// Like `send_request`, but also tears the transfer down - removing
+    /// its handle from the shared `Multi` - as soon as
+    /// `cancellation_token` is cancelled, returning `Error::Cancelled`
+    /// instead of waiting for curl to finish on its own.
+    pub async fn send_request_with_cancellation(
+        &self,
+        easy2: Easy2<H>,
+        cancellation_token: CancellationToken,
+    ) -> Result<Easy2<H>, Error<H>> {
+        let (token, oneshot_receiver) = self.dispatch(easy2).await?;
+        let mut guard = CancelOnDrop::new(token, self.cancel_sender.clone());
+
+        let result = tokio::select! {
+            received = oneshot_receiver => received?,
+            _ = cancellation_token.cancelled() => {
+                let _ = self.cancel_sender.send(token).await;
+                Err(Error::Cancelled)
+            }
+        };
+        guard.disarm();
+        result
+    }
+
+    /// Like `send_request`, but re-attempts the transfer according to
+    /// `policy` when it fails with a transient error (see
+    /// [`RetryPolicy`]) instead of immediately surfacing it to the
+    /// caller.
+    ///
+    /// The `Easy2<H>` passed to a transfer is consumed by it, and `H`
+    /// accumulates response data as the transfer runs, so a failed
+    /// attempt can't simply be resent - `make_easy2` is called once per
+    /// attempt to build a clean request/handler pair to retry with.
+    pub async fn send_request_with_retry(
+        &self,
+        mut make_easy2: impl FnMut() -> Easy2<H>,
+        policy: &RetryPolicy,
+    ) -> Result<Easy2<H>, Error<H>> {
+        let mut attempt = 0;
+        loop {
+            match self.send_request(make_easy2()).await {
+                Ok(easy2) => {
+                    let status = easy2.response_code().unwrap_or(0);
+                    if attempt < policy.max_retries && policy.retryable_status_codes.contains(&status)
+                    {
+                        tokio::time::sleep(policy.backoff_for(attempt)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    return Ok(easy2);
+                }
+                Err(err) if attempt < policy.max_retries && is_retryable_error(&err) => {
+                    tokio::time::sleep(policy.backoff_for(attempt)).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Common surface for anything that can perform a `send_request` call.
+/// `CurlActor` implements it for production use, and, behind the
+/// `test-util` feature, so does `crate::test::MockCurlActor` - code
+/// that depends on sending curl requests can be written against this
+/// trait and exercised against the mock in tests instead of the
+/// network.
+pub trait CurlActorHandle<H: Handler + Debug + Send + 'static> {
+    fn send_request(
+        &self,
+        easy2: Easy2<H>,
+    ) -> impl Future<Output = Result<Easy2<H>, Error<H>>> + Send;
+}
+
+impl<H> CurlActorHandle<H> for CurlActor<H>
+where
+    H: Handler + Debug + Send + 'static,
+{
+    fn send_request(
+        &self,
+        easy2: Easy2<H>,
+    ) -> impl Future<Output = Result<Easy2<H>, Error<H>>> + Send {
+        CurlActor::send_request(self, easy2)
+    }
+}
+
+/// Configuration for `CurlActor::send_request_with_retry`: which
+/// failures are worth retrying, and how long to wait between attempts.
+///
+/// A failed attempt sleeps for
+/// `min(initial_backoff * backoff_multiplier^attempt, max_backoff)`
+/// before the next one, up to `max_retries` retries (so at most
+/// `max_retries + 1` attempts in all).
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub backoff_multiplier: f64,
+    pub max_backoff: Duration,
+    /// HTTP status codes that are treated as transient failures and
+    /// retried even though the transfer itself succeeded.
+    pub retryable_status_codes: Vec<u32>,
+}
+
+impl Default for RetryPolicy {
+    /// Four retries with a 200ms initial backoff doubling up to 10s,
+    /// matching common CI retry setups, retrying 502/503/504 responses.
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            initial_backoff: Duration::from_millis(200),
+            backoff_multiplier: 2.0,
+            max_backoff: Duration::from_secs(10),
+            retryable_status_codes: vec![502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        let backoff =
+            self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(attempt as i32);
+        Duration::from_secs_f64(backoff).min(self.max_backoff)
+    }
+}
+
+/// Classifies a `send_request` failure as transient (connection
+/// refused, DNS resolution failure, a timeout) versus fatal. Only
+/// transient failures are worth retrying; anything else (e.g. a
+/// malformed URL) will just fail the same way again.
+fn is_retryable_error<H: Handler + Debug + Send + 'static>(err: &Error<H>) -> bool {
+    match err {
+        Error::Curl(curl_err) => {
+            curl_err.is_couldnt_connect()
+                || curl_err.is_couldnt_resolve_host()
+                || curl_err.is_couldnt_resolve_proxy()
+                || curl_err.is_operation_timedout()
+        }
+        // `MultiError` codes (e.g. a bad easy handle, an internal state
+        // mismatch) reflect a problem with how this actor is driving
+        // libcurl's multi interface, not a transient condition of the
+        // transfer itself - retrying the same handle against the same
+        // `Multi` would just hit the same error again, so these are
+        // always treated as fatal.
+        Error::Multi(_) => false,
+        _ => false,
     }
 }
 
 #[derive(Debug)]
 pub struct Request<H: Handler + Debug + Send + 'static>(
+    usize,
     Easy2<H>,
     oneshot::Sender<Result<Easy2<H>, Error<H>>>,
+    Option<OwnedSemaphorePermit>,
 );
 
-/// This will perform the sending of the built Easy2
-/// request to the target server.
-#[cfg(feature = "multi")]
-fn perform_curl<H: Handler + Debug + Send + 'static>(
-    easy2: Easy2<H>,
-) -> Result<Easy2<H>, Error<H>> {
-    let multi = Multi::new();
-    let handle = multi.add2(easy2)?;
-
-    while multi.perform()? != 0 {
-        let timeout_result = multi
-            .get_timeout()
-            .map(|d| d.unwrap_or_else(|| Duration::from_secs(2)));
+/// Asks the event loop to drop an in-flight transfer's handle, unless
+/// `completed` was set before the guard is dropped - i.e. the transfer
+/// had already finished and there is nothing left to cancel.
+struct CancelOnDrop {
+    token: usize,
+    cancel_sender: Sender<usize>,
+    completed: bool,
+}
+
+impl CancelOnDrop {
+    fn new(token: usize, cancel_sender: Sender<usize>) -> Self {
+        Self {
+            token,
+            cancel_sender,
+            completed: false,
+        }
+    }
+
+    fn disarm(&mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if !self.completed {
+            let _ = self.cancel_sender.try_send(self.token);
+        }
+    }
+}
+
+/// Bookkeeping kept for each transfer that has been handed to the
+/// `Multi`: the handle curl needs to finish (or remove) the transfer,
+/// the oneshot used to hand the outcome back to the waiting
+/// `send_request` caller, and the concurrency permit (if any) that was
+/// acquired for it. Holding the permit here rather than on the caller's
+/// side ties its release to the handle actually being removed from
+/// `Multi`, instead of to the caller's future merely returning.
+struct InFlight<H: Handler + Debug + Send + 'static> {
+    handle: Easy2Handle<H>,
+    responder: oneshot::Sender<Result<Easy2<H>, Error<H>>>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Drives a single, long-lived `Multi` for the lifetime of the actor.
+///
+/// Every incoming `Request` is added to `multi` immediately via `add2`
+/// and tracked in `in_flight`, keyed by the token the caller allocated
+/// for it and set on the handle with `set_token`. The loop then
+/// repeatedly drains `cancel_receiver` for transfers that timed out or
+/// were cancelled, calls `multi.perform()`, drains `multi.messages(...)`
+/// for transfers that completed, routes each result back to its
+/// `oneshot::Sender`, and blocks in `multi.wait(...)` for whatever
+/// duration `multi.get_timeout()` reports curl actually needs - instead
+/// of a fixed `sleep`.
+///
+/// A cancel can arrive for a token that hasn't been registered into
+/// `in_flight` yet (the request is still sitting in `request_receiver`
+/// while this thread is busy inside `multi.perform()`/`multi.wait()`
+/// for other transfers), so cancels that don't match anything in
+/// `in_flight` are kept in `pending_cancels` and consulted again right
+/// before a newly dequeued request would otherwise be added to `multi`
+/// - at which point it's dropped instead of being started.
+fn multi_event_loop<H: Handler + Debug + Send + 'static>(
+    mut request_receiver: mpsc::Receiver<Request<H>>,
+    mut cancel_receiver: mpsc::Receiver<usize>,
+    max_host_connections: Option<usize>,
+    max_total_connections: Option<usize>,
+) {
+    let mut multi = Multi::new();
+    if let Some(max_host_connections) = max_host_connections {
+        let _ = multi.set_max_host_connections(max_host_connections);
+    }
+    if let Some(max_total_connections) = max_total_connections {
+        let _ = multi.set_max_total_connections(max_total_connections);
+    }
+    let mut in_flight: HashMap<usize, InFlight<H>> = HashMap::new();
+    let mut pending_cancels: HashSet<usize> = HashSet::new();
+    let mut channel_open = true;
+
+    while channel_open || !in_flight.is_empty() {
+        while let Ok(token) = cancel_receiver.try_recv() {
+            if let Some(InFlight { handle, permit, .. }) = in_flight.remove(&token) {
+                let _ = multi.remove2(handle);
+                drop(permit);
+            } else {
+                pending_cancels.insert(token);
+            }
+        }
+
+        loop {
+            let request = if channel_open && in_flight.is_empty() {
+                // Nothing to drive right now, so it's fine to block
+                // this thread until the next request arrives.
+                match request_receiver.blocking_recv() {
+                    Some(request) => request,
+                    None => {
+                        channel_open = false;
+                        break;
+                    }
+                }
+            } else {
+                match request_receiver.try_recv() {
+                    Ok(request) => request,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        channel_open = false;
+                        break;
+                    }
+                }
+            };
+
+            let Request(token, easy2, responder, permit) = request;
 
-        let timeout = match timeout_result {
-            Ok(duration) => duration,
-            Err(multi_error) => {
-                if !multi_error.is_call_perform() {
-                    return Err(Error::from(multi_error));
+            if pending_cancels.remove(&token) {
+                // Cancelled (timed out or explicitly) before it was
+                // ever added to Multi - there's nothing to start.
+                let _ = responder.send(Err(Error::Cancelled));
+                drop(permit);
+                continue;
+            }
+
+            match multi.add2(easy2) {
+                Ok(mut handle) => match handle.set_token(token) {
+                    Ok(()) => {
+                        in_flight.insert(
+                            token,
+                            InFlight {
+                                handle,
+                                responder,
+                                permit,
+                            },
+                        );
+                    }
+                    Err(err) => {
+                        let _ = multi.remove2(handle);
+                        let _ = responder.send(Err(Error::from(err)));
+                        drop(permit);
+                    }
+                },
+                Err(err) => {
+                    let _ = responder.send(Err(Error::from(err)));
+                    drop(permit);
                 }
-                Duration::ZERO
             }
-        };
+        }
+
+        if in_flight.is_empty() {
+            continue;
+        }
+
+        if let Err(err) = multi.perform() {
+            if !err.is_call_perform() {
+                fail_all(&multi, &mut in_flight, err);
+            }
+            continue;
+        }
+
+        let mut finished = Vec::new();
+        multi.messages(|message| {
+            if let Ok(token) = message.token() {
+                if let Some(result) = message.result() {
+                    finished.push((token, result));
+                }
+            }
+        });
+
+        for (token, result) in finished {
+            if let Some(InFlight {
+                handle,
+                responder,
+                permit,
+            }) = in_flight.remove(&token)
+            {
+                let response = match result {
+                    Ok(()) => multi.remove2(handle).map_err(Error::from),
+                    Err(err) => {
+                        let _ = multi.remove2(handle);
+                        Err(Error::from(err))
+                    }
+                };
+                drop(permit);
+                let _ = responder.send(response);
+            }
+        }
+
+        let timeout = multi
+            .get_timeout()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| Duration::from_millis(200));
 
-        if !timeout.is_zero() {
-            sleep(Duration::from_millis(200));
+        if let Err(err) = multi.wait(&mut [], timeout) {
+            fail_all(&multi, &mut in_flight, err);
         }
     }
-    multi.remove2(handle).map_err(Error::from)
 }
 
-/// This will perform the sending of the built Easy2
-/// request to the target server.
-#[cfg(not(feature = "multi"))]
-fn perform_curl<H: Handler + Debug + Send + 'static>(
-    easy2: Easy2<H>,
-) -> Result<Easy2<H>, Error<H>> {
-    easy2.perform().map_err(Error::from)?;
-    Ok(easy2)
+/// Tears down every in-flight transfer with the same fatal `MultiError`,
+/// used when the shared `Multi` itself reports a non-recoverable error
+/// (i.e. `perform`/`wait` failing with something other than
+/// "call perform again").
+fn fail_all<H: Handler + Debug + Send + 'static>(
+    multi: &Multi,
+    in_flight: &mut HashMap<usize, InFlight<H>>,
+    err: MultiError,
+) {
+    for (
+        _,
+        InFlight {
+            handle,
+            responder,
+            permit,
+        },
+    ) in in_flight.drain()
+    {
+        let _ = multi.remove2(handle);
+        let _ = responder.send(Err(Error::from(err.clone())));
+        drop(permit);
+    }
 }
 
 #[cfg(test)]
 mod test {
 
+    use std::time::{Duration, Instant};
+
     use http::StatusCode;
     use wiremock::matchers::method;
     use wiremock::matchers::path;
@@ -206,6 +688,7 @@ mod test {
 
     use crate::actor::CurlActor;
     use crate::actor::Easy2;
+    use crate::actor::RetryPolicy;
     use crate::response_handler::ResponseHandler;
     use std::convert::TryFrom;
 
@@ -224,7 +707,6 @@ mod test {
     }
 
     #[tokio::test]
-    #[cfg(not(feature = "multi"))]
     async fn test_async_requests() {
         const MOCK_BODY_RESPONSE: &str = r#"{"token":"12345"}"#;
         const MOCK_STATUS_CODE: StatusCode = StatusCode::OK;
@@ -244,7 +726,7 @@ mod test {
 
         let spawn1 = tokio::spawn(async move {
             let result = curl.send_request(easy2).await;
-            let mut result = result.unwrap();
+            let result = result.unwrap();
             // Test response body
             assert_eq!(
                 String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
@@ -267,7 +749,7 @@ mod test {
 
         let spawn2 = tokio::spawn(async move {
             let result = curl.send_request(easy2).await;
-            let mut result = result.unwrap();
+            let result = result.unwrap();
             // Test response body
             assert_eq!(
                 String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
@@ -286,64 +768,256 @@ mod test {
     }
 
     #[tokio::test]
-    #[cfg(feature = "multi")]
-    async fn test_async_requests_multi() {
+    async fn test_concurrent_requests_are_multiplexed_on_one_actor() {
+        const DELAY: Duration = Duration::from_millis(300);
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow-a"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(DELAY))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/slow-b"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(DELAY))
+            .mount(&server)
+            .await;
+
+        let curl = CurlActor::new();
+
+        let mut easy2_a = Easy2::new(ResponseHandler::new());
+        easy2_a
+            .url(&format!("{}{}", server.uri(), "/slow-a"))
+            .unwrap();
+        easy2_a.get(true).unwrap();
+
+        let mut easy2_b = Easy2::new(ResponseHandler::new());
+        easy2_b
+            .url(&format!("{}{}", server.uri(), "/slow-b"))
+            .unwrap();
+        easy2_b.get(true).unwrap();
+
+        let started = Instant::now();
+        let (result_a, result_b) =
+            tokio::join!(curl.send_request(easy2_a), curl.send_request(easy2_b));
+        let elapsed = started.elapsed();
+
+        result_a.unwrap();
+        result_b.unwrap();
+
+        // Both transfers share one Multi, so they should complete in
+        // roughly the time of a single delayed request, not the sum of
+        // both - proving they ran concurrently rather than serially.
+        assert!(
+            elapsed < DELAY * 2,
+            "expected concurrent transfers to finish in well under {:?}, took {:?}",
+            DELAY * 2,
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_serializes_requests_beyond_the_cap() {
+        const DELAY: Duration = Duration::from_millis(300);
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow-a"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(DELAY))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/slow-b"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(DELAY))
+            .mount(&server)
+            .await;
+
+        let curl = CurlActor::with_concurrency_limit(1);
+        assert_eq!(curl.available_permits(), Some(1));
+
+        let mut easy2_a = Easy2::new(ResponseHandler::new());
+        easy2_a
+            .url(&format!("{}{}", server.uri(), "/slow-a"))
+            .unwrap();
+        easy2_a.get(true).unwrap();
+
+        let mut easy2_b = Easy2::new(ResponseHandler::new());
+        easy2_b
+            .url(&format!("{}{}", server.uri(), "/slow-b"))
+            .unwrap();
+        easy2_b.get(true).unwrap();
+
+        let started = Instant::now();
+        let (result_a, result_b) =
+            tokio::join!(curl.send_request(easy2_a), curl.send_request(easy2_b));
+        let elapsed = started.elapsed();
+
+        result_a.unwrap();
+        result_b.unwrap();
+
+        // With only one permit available, the second request must wait
+        // for the first to release its permit, so the two transfers run
+        // one after the other rather than concurrently.
+        assert!(
+            elapsed >= DELAY * 2,
+            "expected requests to serialize on the single permit, took {:?}",
+            elapsed
+        );
+        assert_eq!(curl.available_permits(), Some(1));
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_doubles_until_the_cap() {
+        let policy = RetryPolicy {
+            max_retries: 4,
+            initial_backoff: Duration::from_millis(100),
+            backoff_multiplier: 2.0,
+            max_backoff: Duration::from_millis(350),
+            retryable_status_codes: vec![503],
+        };
+
+        assert_eq!(policy.backoff_for(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(200));
+        // Would be 400ms uncapped, but max_backoff clamps it to 350ms.
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(350));
+    }
+
+    #[tokio::test]
+    async fn test_send_request_with_retry_retries_a_retryable_status_code() {
         const MOCK_BODY_RESPONSE: &str = r#"{"token":"12345"}"#;
-        const MOCK_STATUS_CODE: StatusCode = StatusCode::OK;
 
-        let server = start_mock_server(
-            "/async-test",
-            MOCK_BODY_RESPONSE.to_string(),
-            StatusCode::OK,
-        )
-        .await;
-        let url = format!("{}{}", server.uri(), "/async-test");
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/retry-test"))
+            .respond_with(ResponseTemplate::new(StatusCode::SERVICE_UNAVAILABLE))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/retry-test"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_string(MOCK_BODY_RESPONSE),
+            )
+            .mount(&server)
+            .await;
+
+        let url = format!("{}{}", server.uri(), "/retry-test");
+        let curl = CurlActor::new();
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        };
+
+        let result = curl
+            .send_request_with_retry(
+                || {
+                    let mut easy2 = Easy2::new(ResponseHandler::new());
+                    easy2.url(url.as_str()).unwrap();
+                    easy2.get(true).unwrap();
+                    easy2
+                },
+                &policy,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
+            MOCK_BODY_RESPONSE.to_string()
+        );
+        assert_eq!(result.response_code().unwrap(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_send_request_with_timeout_aborts_a_slow_transfer() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/too-slow"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        let url = format!("{}{}", server.uri(), "/too-slow");
 
         let curl = CurlActor::new();
         let mut easy2 = Easy2::new(ResponseHandler::new());
         easy2.url(url.as_str()).unwrap();
         easy2.get(true).unwrap();
 
-        let spawn1 = tokio::spawn(async move {
-            let result = curl.send_request(easy2).await;
-            let mut result = result.unwrap();
-            // Test response body
-            assert_eq!(
-                String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
-                MOCK_BODY_RESPONSE.to_string()
-            );
+        let started = Instant::now();
+        let result = curl
+            .send_request_with_timeout(easy2, Duration::from_millis(100))
+            .await;
+        let elapsed = started.elapsed();
+
+        assert!(matches!(result, Err(crate::error::Error::Timeout)));
+        assert!(
+            elapsed < Duration::from_secs(1),
+            "expected send_request_with_timeout to return promptly, took {:?}",
+            elapsed
+        );
+    }
 
-            // Test response status code
-            let status_code = result.response_code().unwrap();
+    #[tokio::test]
+    async fn test_send_request_with_cancellation_aborts_a_cancelled_transfer() {
+        use tokio_util::sync::CancellationToken;
 
-            assert_eq!(
-                StatusCode::from_u16(u16::try_from(status_code).unwrap()).unwrap(),
-                MOCK_STATUS_CODE
-            );
-        });
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/too-slow"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        let url = format!("{}{}", server.uri(), "/too-slow");
 
         let curl = CurlActor::new();
         let mut easy2 = Easy2::new(ResponseHandler::new());
         easy2.url(url.as_str()).unwrap();
         easy2.get(true).unwrap();
 
-        let spawn2 = tokio::spawn(async move {
-            let result = curl.send_request(easy2).await;
-            let mut result = result.unwrap();
-            // Test response body
-            assert_eq!(
-                String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
-                MOCK_BODY_RESPONSE.to_string()
-            );
-
-            // Test response status code
-            let status_code = result.response_code().unwrap();
-            assert_eq!(
-                StatusCode::from_u16(u16::try_from(status_code).unwrap()).unwrap(),
-                MOCK_STATUS_CODE
-            );
+        let cancellation_token = CancellationToken::new();
+        let cancel_it = cancellation_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel_it.cancel();
         });
 
-        let (_, _) = tokio::join!(spawn1, spawn2);
+        let started = Instant::now();
+        let result = curl
+            .send_request_with_cancellation(easy2, cancellation_token)
+            .await;
+        let elapsed = started.elapsed();
+
+        assert!(matches!(result, Err(crate::error::Error::Cancelled)));
+        assert!(
+            elapsed < Duration::from_secs(1),
+            "expected send_request_with_cancellation to return promptly, took {:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_limits_still_serve_requests() {
+        const MOCK_BODY_RESPONSE: &str = r#"{"token":"12345"}"#;
+
+        let server = start_mock_server(
+            "/async-test",
+            MOCK_BODY_RESPONSE.to_string(),
+            StatusCode::OK,
+        )
+        .await;
+        let url = format!("{}{}", server.uri(), "/async-test");
+
+        let curl = CurlActor::with_connection_limits(4, 8);
+        let mut easy2 = Easy2::new(ResponseHandler::new());
+        easy2.url(url.as_str()).unwrap();
+        easy2.get(true).unwrap();
+
+        let result = curl.send_request(easy2).await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&result.get_ref().to_owned().get_data()),
+            MOCK_BODY_RESPONSE.to_string()
+        );
+        assert_eq!(result.response_code().unwrap(), 200);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+use curl::easy::Handler;
+use curl::Error as CurlError;
+use curl::MultiError;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::oneshot::error::RecvError;
+
+use crate::actor::Request;
+
+/// Errors that can occur while sending a request through a `CurlActor`.
+#[derive(Debug)]
+pub enum Error<H: Handler + fmt::Debug + Send + 'static> {
+    /// A libcurl error from a single transfer (`Easy2`).
+    Curl(CurlError),
+    /// A libcurl multi-interface error from the shared `Multi`.
+    Multi(MultiError),
+    /// The actor's background event loop is no longer running; carries
+    /// the request that couldn't be delivered to it.
+    TokioSend(SendError<Request<H>>),
+    /// The event loop dropped the response channel before answering.
+    TokioRecv(RecvError),
+    /// `send_request_with_timeout`'s deadline elapsed before the
+    /// transfer finished.
+    Timeout,
+    /// The `CancellationToken` passed to
+    /// `send_request_with_cancellation` was cancelled before the
+    /// transfer finished.
+    Cancelled,
+}
+
+impl<H: Handler + fmt::Debug + Send + 'static> fmt::Display for Error<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Curl(err) => write!(f, "curl error: {err}"),
+            Error::Multi(err) => write!(f, "curl multi error: {err}"),
+            Error::TokioSend(_) => write!(f, "the CurlActor's request channel is closed"),
+            Error::TokioRecv(_) => write!(f, "the CurlActor dropped the response channel"),
+            Error::Timeout => write!(f, "the request timed out"),
+            Error::Cancelled => write!(f, "the request was cancelled"),
+        }
+    }
+}
+
+impl<H: Handler + fmt::Debug + Send + 'static> std::error::Error for Error<H> {}
+
+impl<H: Handler + fmt::Debug + Send + 'static> From<CurlError> for Error<H> {
+    fn from(err: CurlError) -> Self {
+        Error::Curl(err)
+    }
+}
+
+impl<H: Handler + fmt::Debug + Send + 'static> From<MultiError> for Error<H> {
+    fn from(err: MultiError) -> Self {
+        Error::Multi(err)
+    }
+}
+
+impl<H: Handler + fmt::Debug + Send + 'static> From<SendError<Request<H>>> for Error<H> {
+    fn from(err: SendError<Request<H>>) -> Self {
+        Error::TokioSend(err)
+    }
+}
+
+impl<H: Handler + fmt::Debug + Send + 'static> From<RecvError> for Error<H> {
+    fn from(err: RecvError) -> Self {
+        Error::TokioRecv(err)
+    }
+}